@@ -8,6 +8,7 @@ pub(crate) const HEADER_IDENTIFIER_SEGMENT_HEADER: u32 = 0x7A666673;
 pub(crate) const HEADER_IDENTIFIER_COMPRESSION_HEADER: u32 = 0x7A666663;
 pub(crate) const HEADER_IDENTIFIER_PBE_HEADER: u32 = 0x7A666670;
 pub(crate) const HEADER_IDENTIFIER_ENCRYPTION_HEADER: u32 = 0x7A666665;
+pub(crate) const HEADER_IDENTIFIER_KEY_SLOT: u32 = 0x7a666b73;
 pub(crate) const HEADER_IDENTIFIER_CHUNK_HEADER: u32 = 0x7A666643;
 pub(crate) const HEADER_IDENTIFIER_HASH_HEADER: u32 = 0x7a666668;
 pub(crate) const HEADER_IDENTIFIER_HASH_VALUE: u32 = 0x7a666648;
@@ -21,6 +22,7 @@ pub(crate) const FOOTER_IDENTIFIER_OBJECT_FOOTER_LOGICAL: u32 = 0x7A66664C;
 pub(crate) const FOOTER_IDENTIFIER_FILE_FOOTER: u32 = 0x7A666649;
 
 pub(crate) const PBE_KDF_PARAMETERS: u32 = 0x6b646670;
+pub(crate) const PBE_KDF_PARAMETERS_ARGON2ID: u32 = 0x6b646671;
 
 // Encoding keys
 pub(crate) const ENCODING_KEY_CASE_NUMBER: &str = "cn";
@@ -46,6 +48,10 @@ pub(crate) const ERROR_HEADER_DECODER_HEADER_LENGTH: &'static str = "Unable to r
 pub(crate) const ERROR_HEADER_DECODER_KEY_POSITION: &'static str = "Key not in position.";
 pub(crate) const ERROR_HEADER_DECODER_COMPRESSION_ALGORITHM: &'static str = "unknown compression algorithm value";
 pub(crate) const ERROR_HEADER_DECODER_MISMATCH_IDENTIFIER: &'static str = "The read identifier does not match the header identifier.";
+pub(crate) const ERROR_HEADER_DECODER_INVALID_KDF_PARAMETER: &'static str = "Invalid KDF parameter (cost factor of zero).";
+pub(crate) const ERROR_ENCRYPTION_NO_MATCHING_KEYSLOT: &'static str = "No keyslot could be unlocked with the given password.";
+pub(crate) const ERROR_HEADER_DECODER_UNKNOWN_ENCRYPTION_MODE: &'static str = "Unknown encryption mode value.";
+pub(crate) const ERROR_ENCRYPTION_CHUNK_NUMBER_OVERFLOW: &'static str = "Chunk number exceeds the maximum value supported by the per-chunk nonce scheme (2^32 - 1).";
 pub(crate) const ERROR_HEADER_DECODER_MAIN_HEADER_ENCRYPTED: &'static str = "The main header is encrypted.";
 pub(crate) const ERROR_HEADER_DECODER_MAIN_HEADER_NOT_ENCRYPTED: &'static str = "The main header is not encrypted.";
 pub(crate) const ERROR_MISSING_SEGMENT: &'static str = "A segment is missing.";
@@ -0,0 +1,324 @@
+// - external
+use aes::{Aes128, Aes256};
+use cbc::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit, block_padding::Pkcs7};
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+use argon2::Argon2;
+use serde::Serialize;
+use aead::{Aead, KeyInit, Payload};
+use aes_gcm_siv::{Aes128GcmSiv, Aes256GcmSiv};
+use chacha20poly1305::ChaCha20Poly1305;
+
+// - internal
+use crate::{
+	Result,
+	ZffError,
+	ERROR_ENCRYPTION_CHUNK_NUMBER_OVERFLOW,
+};
+
+/// Defines the algorithm, used to encrypt/decrypt the chunk (and, optionally, header) data.
+#[derive(Debug,Clone,Serialize)]
+pub enum EncryptionAlgorithm {
+	/// AES128 in GCM-SIV mode.
+	AES128GCMSIV = 0,
+	/// AES256 in GCM-SIV mode.
+	AES256GCMSIV = 1,
+	/// ChaCha20-Poly1305, useful on platforms without AES hardware acceleration.
+	ChaCha20Poly1305 = 2,
+}
+
+/// This struct contains several cryptographic methods, e.g. to derive a key from a password
+/// (used to encrypt/decrypt the encryption key stored in the [crate::header::EncryptionHeader])
+/// or to encrypt/decrypt the chunk/header data itself.
+pub struct Encryption;
+
+impl Encryption {
+	/// derives the encryption key for the given password with PBKDF2-HMAC-SHA256 and decrypts
+	/// the given encrypted key with AES128-CBC.
+	pub fn decrypt_pbkdf2sha256_aes128cbc<P, K>(
+		iterations: u16,
+		salt: &[u8; 32],
+		iv: &[u8; 16],
+		password: P,
+		encrypted_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 16];
+		pbkdf2_hmac::<Sha256>(password.as_ref(), salt, iterations as u32, &mut derived_key);
+		Self::decrypt_aes128cbc(&derived_key, iv, encrypted_key)
+	}
+
+	/// derives the encryption key for the given password with PBKDF2-HMAC-SHA256 and decrypts
+	/// the given encrypted key with AES256-CBC.
+	pub fn decrypt_pbkdf2sha256_aes256cbc<P, K>(
+		iterations: u16,
+		salt: &[u8; 32],
+		iv: &[u8; 16],
+		password: P,
+		encrypted_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 32];
+		pbkdf2_hmac::<Sha256>(password.as_ref(), salt, iterations as u32, &mut derived_key);
+		Self::decrypt_aes256cbc(&derived_key, iv, encrypted_key)
+	}
+
+	/// derives the encryption key for the given password with Argon2id and decrypts the given
+	/// encrypted key with AES128-CBC.
+	pub fn decrypt_argon2id_aes128cbc<P, K>(
+		m_cost: u32,
+		t_cost: u32,
+		p_cost: u32,
+		salt: &[u8],
+		iv: &[u8; 16],
+		password: P,
+		encrypted_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 16];
+		Self::derive_argon2id(m_cost, t_cost, p_cost, salt, password, &mut derived_key)?;
+		Self::decrypt_aes128cbc(&derived_key, iv, encrypted_key)
+	}
+
+	/// derives the encryption key for the given password with Argon2id and decrypts the given
+	/// encrypted key with AES256-CBC.
+	pub fn decrypt_argon2id_aes256cbc<P, K>(
+		m_cost: u32,
+		t_cost: u32,
+		p_cost: u32,
+		salt: &[u8],
+		iv: &[u8; 16],
+		password: P,
+		encrypted_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 32];
+		Self::derive_argon2id(m_cost, t_cost, p_cost, salt, password, &mut derived_key)?;
+		Self::decrypt_aes256cbc(&derived_key, iv, encrypted_key)
+	}
+
+	/// derives the wrapping key for the given password with PBKDF2-HMAC-SHA256 and encrypts
+	/// `master_key` with AES128-CBC, so it can be stored in a new keyslot.
+	pub fn encrypt_pbkdf2sha256_aes128cbc<P, K>(
+		iterations: u16,
+		salt: &[u8; 32],
+		iv: &[u8; 16],
+		password: P,
+		master_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 16];
+		pbkdf2_hmac::<Sha256>(password.as_ref(), salt, iterations as u32, &mut derived_key);
+		Self::encrypt_aes128cbc(&derived_key, iv, master_key)
+	}
+
+	/// derives the wrapping key for the given password with PBKDF2-HMAC-SHA256 and encrypts
+	/// `master_key` with AES256-CBC, so it can be stored in a new keyslot.
+	pub fn encrypt_pbkdf2sha256_aes256cbc<P, K>(
+		iterations: u16,
+		salt: &[u8; 32],
+		iv: &[u8; 16],
+		password: P,
+		master_key: K,
+		) -> Result<Vec<u8>>
+	where
+		P: AsRef<[u8]>,
+		K: AsRef<[u8]>,
+	{
+		let mut derived_key = [0u8; 32];
+		pbkdf2_hmac::<Sha256>(password.as_ref(), salt, iterations as u32, &mut derived_key);
+		Self::encrypt_aes256cbc(&derived_key, iv, master_key)
+	}
+
+	fn encrypt_aes128cbc<K: AsRef<[u8]>>(key: &[u8; 16], iv: &[u8; 16], data: K) -> Result<Vec<u8>> {
+		Ok(cbc::Encryptor::<Aes128>::new(key.into(), iv.into()).encrypt_padded_vec_mut::<Pkcs7>(data.as_ref()))
+	}
+
+	fn encrypt_aes256cbc<K: AsRef<[u8]>>(key: &[u8; 32], iv: &[u8; 16], data: K) -> Result<Vec<u8>> {
+		Ok(cbc::Encryptor::<Aes256>::new(key.into(), iv.into()).encrypt_padded_vec_mut::<Pkcs7>(data.as_ref()))
+	}
+
+	fn derive_argon2id<P>(m_cost: u32, t_cost: u32, p_cost: u32, salt: &[u8], password: P, output: &mut [u8]) -> Result<()>
+	where
+		P: AsRef<[u8]>,
+	{
+		let params = argon2::Params::new(m_cost, t_cost, p_cost, Some(output.len()))
+			.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+		let argon2 = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+		argon2.hash_password_into(password.as_ref(), salt, output)
+			.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+		Ok(())
+	}
+
+	fn decrypt_aes128cbc<K: AsRef<[u8]>>(key: &[u8; 16], iv: &[u8; 16], encrypted_data: K) -> Result<Vec<u8>> {
+		let mut buffer = encrypted_data.as_ref().to_vec();
+		let plaintext = cbc::Decryptor::<Aes128>::new(key.into(), iv.into())
+			.decrypt_padded_mut::<Pkcs7>(&mut buffer)
+			.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+		Ok(plaintext.to_vec())
+	}
+
+	fn decrypt_aes256cbc<K: AsRef<[u8]>>(key: &[u8; 32], iv: &[u8; 16], encrypted_data: K) -> Result<Vec<u8>> {
+		let mut buffer = encrypted_data.as_ref().to_vec();
+		let plaintext = cbc::Decryptor::<Aes256>::new(key.into(), iv.into())
+			.decrypt_padded_mut::<Pkcs7>(&mut buffer)
+			.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+		Ok(plaintext.to_vec())
+	}
+
+	/// derives a unique 12-byte per-chunk nonce from the given 8-byte `base_nonce` and the chunk's
+	/// `chunk_number`, by appending the little-endian `chunk_number` to the base nonce. Since only
+	/// the low 32 bits of `chunk_number` fit into the remaining 4 nonce bytes, this guarantees
+	/// uniqueness only as long as chunk numbers are unique *and* do not exceed `u32::MAX` (2^32 - 1)
+	/// within one image - chunk numbers `n` and `n + 2^32` would otherwise collide to the same
+	/// nonce, which is catastrophic nonce reuse for AES-GCM-SIV/ChaCha20-Poly1305. Images with more
+	/// chunks than that are rejected rather than silently reusing a nonce.
+	pub fn chunk_nonce(base_nonce: &[u8; 8], chunk_number: u64) -> Result<[u8; 12]> {
+		let counter: u32 = chunk_number.try_into()
+			.map_err(|_| ZffError::new_encryption_error(ERROR_ENCRYPTION_CHUNK_NUMBER_OVERFLOW))?;
+		let mut nonce = [0u8; 12];
+		nonce[..8].copy_from_slice(base_nonce);
+		nonce[8..].copy_from_slice(&counter.to_le_bytes());
+		Ok(nonce)
+	}
+
+	/// encrypts the given chunk content with the given algorithm and key, binding `aad`
+	/// (the encoded chunk number and chunk size, see [crate::header::ChunkHeader::encode_aad]) into
+	/// the AEAD authentication tag. The nonce is derived from `base_nonce` and `chunk_number` via
+	/// [Encryption::chunk_nonce], so each chunk is sealed under its own unique nonce.
+	pub fn encrypt_chunk_content<K: AsRef<[u8]>>(
+		algorithm: &EncryptionAlgorithm,
+		key: K,
+		base_nonce: &[u8; 8],
+		chunk_number: u64,
+		aad: &[u8],
+		plaintext: &[u8],
+		) -> Result<Vec<u8>> {
+		let nonce = Self::chunk_nonce(base_nonce, chunk_number)?;
+		let nonce = &nonce;
+		let payload = Payload { msg: plaintext, aad };
+		match algorithm {
+			EncryptionAlgorithm::AES128GCMSIV => {
+				let cipher = Aes128GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::AES256GCMSIV => {
+				let cipher = Aes256GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::ChaCha20Poly1305 => {
+				let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+		}
+	}
+
+	/// decrypts the given chunk content with the given algorithm and key, verifying that `aad`
+	/// (the encoded chunk number and chunk size reconstructed from the stored [crate::header::ChunkHeader])
+	/// matches the associated data authenticated at encryption time. The nonce is reconstructed from
+	/// `base_nonce` and `chunk_number` via [Encryption::chunk_nonce], identically to the encrypt side.
+	pub fn decrypt_chunk_content<K: AsRef<[u8]>>(
+		algorithm: &EncryptionAlgorithm,
+		key: K,
+		base_nonce: &[u8; 8],
+		chunk_number: u64,
+		aad: &[u8],
+		ciphertext: &[u8],
+		) -> Result<Vec<u8>> {
+		let nonce = Self::chunk_nonce(base_nonce, chunk_number)?;
+		let nonce = &nonce;
+		let payload = Payload { msg: ciphertext, aad };
+		match algorithm {
+			EncryptionAlgorithm::AES128GCMSIV => {
+				let cipher = Aes128GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::AES256GCMSIV => {
+				let cipher = Aes256GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::ChaCha20Poly1305 => {
+				let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+		}
+	}
+
+	/// encrypts the given `plaintext` as a single AEAD blob, without any chunking - used for
+	/// [crate::header::EncryptionMode::InMemory], where the body is sealed (and later decrypted)
+	/// in one shot instead of the per-chunk loop used for [crate::header::EncryptionMode::Streamed].
+	pub fn encrypt_message<K: AsRef<[u8]>>(
+		algorithm: &EncryptionAlgorithm,
+		key: K,
+		nonce: &[u8; 12],
+		plaintext: &[u8],
+		) -> Result<Vec<u8>> {
+		let payload = Payload { msg: plaintext, aad: b"" };
+		match algorithm {
+			EncryptionAlgorithm::AES128GCMSIV => {
+				let cipher = Aes128GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::AES256GCMSIV => {
+				let cipher = Aes256GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::ChaCha20Poly1305 => {
+				let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.encrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+		}
+	}
+
+	/// decrypts the given `ciphertext` as a single AEAD blob, the counterpart of [Encryption::encrypt_message]
+	/// used for [crate::header::EncryptionMode::InMemory].
+	pub fn decrypt_message<K: AsRef<[u8]>>(
+		algorithm: &EncryptionAlgorithm,
+		key: K,
+		nonce: &[u8; 12],
+		ciphertext: &[u8],
+		) -> Result<Vec<u8>> {
+		let payload = Payload { msg: ciphertext, aad: b"" };
+		match algorithm {
+			EncryptionAlgorithm::AES128GCMSIV => {
+				let cipher = Aes128GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::AES256GCMSIV => {
+				let cipher = Aes256GcmSiv::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+			EncryptionAlgorithm::ChaCha20Poly1305 => {
+				let cipher = ChaCha20Poly1305::new_from_slice(key.as_ref())
+					.map_err(|e| ZffError::new_encryption_error(e.to_string()))?;
+				cipher.decrypt(nonce.into(), payload).map_err(|e| ZffError::new_encryption_error(e.to_string()))
+			},
+		}
+	}
+}
@@ -47,6 +47,17 @@ impl ChunkHeader {
 	pub fn chunk_number(&self) -> u64 {
 		self.chunk_number
 	}
+
+	/// returns the associated data to authenticate together with this chunk's encrypted content.\
+	/// This binds the ciphertext to the chunk's position (`chunk_number`) and its `chunk_size`, so that
+	/// reordering or splicing encrypted chunks is detected as an AEAD authentication failure on decrypt,
+	/// rather than being accepted as silently corrupt-but-valid data.
+	pub fn encode_aad(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+		vec.extend_from_slice(&self.chunk_number.to_le_bytes());
+		vec.extend_from_slice(&self.chunk_size.to_le_bytes());
+		vec
+	}
 }
 
 impl HeaderObject for ChunkHeader {
@@ -11,30 +11,61 @@ use crate::{
 	ValueEncoder,
 	ValueDecoder,
 	header::PBEHeader,
+	header::KeySlot,
 	ZffError,
 	KDFScheme,
 	PBEScheme,
 	header::KDFParameters,
+	header::PBKDF2SHA256Parameters,
 	Encryption,
 };
 
 use crate::{
 	HEADER_IDENTIFIER_ENCRYPTION_HEADER,
 	ERROR_HEADER_DECODER_UNKNOWN_ENCRYPTION_ALGORITHM,
+	ERROR_HEADER_DECODER_UNKNOWN_PBE_SCHEME,
+	ERROR_HEADER_DECODER_UNKNOWN_ENCRYPTION_MODE,
+	ERROR_ENCRYPTION_NO_MATCHING_KEYSLOT,
 };
 
 // - external
 use serde::ser::{Serialize, Serializer, SerializeStruct};
 use hex::ToHex;
+use rand::RngCore;
+use rand::rngs::OsRng;
+
+/// Distinguishes how the body belonging to this header was (or should be) sealed: as a single
+/// in-memory AEAD blob ([EncryptionMode::InMemory]) or as a sequence of independently-authenticated
+/// chunks ([EncryptionMode::Streamed], see [crate::Encryption::encrypt_chunk_content]).
+#[derive(Debug,Clone,PartialEq,Eq,serde::Serialize)]
+pub enum EncryptionMode {
+	/// the body is sealed as a sequence of independently-authenticated chunks.
+	Streamed,
+	/// the body is sealed as a single in-memory AEAD blob.
+	InMemory,
+}
+
+impl EncryptionMode {
+	pub fn get_value(&self) -> u8 {
+		match self {
+			EncryptionMode::Streamed => 0,
+			EncryptionMode::InMemory => 1,
+		}
+	}
+}
 
 /// The encryption header contains all informations (and the **encrypted** key) for the data and header encryption.\
-/// The encryption header is the only optional header part of the main header.
+/// The encryption header is the only optional header part of the main header.\
+/// The master encryption key is not bound to a single password: the header carries a [Vec] of
+/// [KeySlot]s, each wrapping its own copy of the same master key under its own password/KDF
+/// parameters, so an image can be unlocked by several independent passwords.
 #[derive(Debug,Clone)]
 pub struct EncryptionHeader {
 	header_version: u8,
-	pbe_header: PBEHeader,
+	key_slots: Vec<KeySlot>,
 	algorithm: EncryptionAlgorithm,
-	encrypted_encryption_key: Vec<u8>,
+	mode: EncryptionMode,
+	encryption_base_nonce: [u8; 8],
 	encrypted_header_nonce: [u8; 12],
 }
 
@@ -42,16 +73,18 @@ impl EncryptionHeader {
 	/// creates a new encryption header by the given values.
 	pub fn new(
 		header_version: u8,
-		pbe_header: PBEHeader,
+		key_slots: Vec<KeySlot>,
 		algorithm: EncryptionAlgorithm,
-		encrypted_encryption_key: Vec<u8>, //encrypted with set password
+		mode: EncryptionMode,
+		encryption_base_nonce: [u8; 8], //used to derive the per-chunk nonces, see Encryption::chunk_nonce
 		encrypted_header_nonce: [u8; 12], //used for header encryption
 		) -> EncryptionHeader {
 		Self {
 			header_version: header_version,
-			pbe_header: pbe_header,
+			key_slots: key_slots,
 			algorithm: algorithm,
-			encrypted_encryption_key: encrypted_encryption_key,
+			mode: mode,
+			encryption_base_nonce: encryption_base_nonce,
 			encrypted_header_nonce: encrypted_header_nonce
 		}
 	}
@@ -61,9 +94,21 @@ impl EncryptionHeader {
 		&self.algorithm
 	}
 
-	/// returns a reference to the inner PBE header.
-	pub fn pbe_header(&self) -> &PBEHeader {
-		&self.pbe_header
+	/// returns the encryption mode (whether the body is sealed as a single in-memory AEAD blob
+	/// or as a sequence of independently-authenticated chunks).
+	pub fn mode(&self) -> &EncryptionMode {
+		&self.mode
+	}
+
+	/// returns a reference to the keyslots of this encryption header.
+	pub fn key_slots(&self) -> &Vec<KeySlot> {
+		&self.key_slots
+	}
+
+	/// returns the base nonce, used to derive a unique per-chunk nonce for each encrypted chunk
+	/// (see [crate::Encryption::chunk_nonce]).
+	pub fn encryption_base_nonce(&self) -> &[u8; 8] {
+		&self.encryption_base_nonce
 	}
 
 	/// returns the nonce, used for header encryption
@@ -71,34 +116,36 @@ impl EncryptionHeader {
 		&self.encrypted_header_nonce
 	}
 
-	/// tries to decrypt the encryption key
-	pub fn decrypt_encryption_key<P: AsRef<[u8]>>(&self, password: P) -> Result<Vec<u8>> {
-		match self.pbe_header.kdf_scheme() {
-			KDFScheme::PBKDF2SHA256 => match self.pbe_header.kdf_parameters() {
-				KDFParameters::PBKDF2SHA256Parameters(parameters) => {
-					let iterations = parameters.iterations();
-					let salt = parameters.salt();
-
-					match self.pbe_header.encryption_scheme() {
-						PBEScheme::AES128CBC => Encryption::decrypt_pbkdf2sha256_aes128cbc(
-							iterations,
-							salt,
-							self.pbe_header.nonce(),
-							password,
-							&self.encrypted_encryption_key
-							),
-						PBEScheme::AES256CBC => Encryption::decrypt_pbkdf2sha256_aes256cbc(
-							iterations,
-							salt,
-							self.pbe_header.nonce(),
-							password,
-							&self.encrypted_encryption_key
-							),
-					}
-				}
-				
+	/// tries to decrypt the encryption key by trying each keyslot with the given password, in
+	/// order, and returning the master key from the first keyslot that unwraps successfully.
+	pub fn decrypt_encryption_key<P: AsRef<[u8]> + Clone>(&self, password: P) -> Result<Vec<u8>> {
+		for key_slot in &self.key_slots {
+			if let Ok(master_key) = key_slot.decrypt_encryption_key(password.clone()) {
+				return Ok(master_key);
 			}
 		}
+		Err(ZffError::new_header_decode_error(ERROR_ENCRYPTION_NO_MATCHING_KEYSLOT))
+	}
+
+	/// wraps the already unlocked `master_key` under a freshly derived PBKDF2-SHA256 key for the
+	/// given `password` and appends it as a new keyslot, so a second investigator can be granted
+	/// access to an already sealed image without re-encrypting it.
+	pub fn add_keyslot<P: AsRef<[u8]>>(&mut self, master_key: &[u8], password: P, iterations: u16) -> Result<()> {
+		let mut salt = [0u8; 32];
+		OsRng.fill_bytes(&mut salt);
+		let mut nonce = [0u8; 16];
+		OsRng.fill_bytes(&mut nonce);
+
+		let (encryption_scheme, encrypted_encryption_key) = match master_key.len() {
+			16 => (PBEScheme::AES128CBC, Encryption::encrypt_pbkdf2sha256_aes128cbc(iterations, &salt, &nonce, password, master_key)?),
+			32 => (PBEScheme::AES256CBC, Encryption::encrypt_pbkdf2sha256_aes256cbc(iterations, &salt, &nonce, password, master_key)?),
+			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_PBE_SCHEME)),
+		};
+
+		let kdf_parameters = KDFParameters::PBKDF2SHA256Parameters(PBKDF2SHA256Parameters::new(iterations, salt));
+		let pbe_header = PBEHeader::new(self.header_version, KDFScheme::PBKDF2SHA256, encryption_scheme, kdf_parameters, nonce);
+		self.key_slots.push(KeySlot::new(pbe_header, encrypted_encryption_key));
+		Ok(())
 	}
 }
 
@@ -110,9 +157,13 @@ impl HeaderObject for EncryptionHeader {
 		let mut vec = Vec::new();
 
 		vec.push(self.header_version);
-		vec.append(&mut self.pbe_header.encode_directly());
+		vec.append(&mut (self.key_slots.len() as u64).encode_directly());
+		for key_slot in &self.key_slots {
+			vec.append(&mut key_slot.encode_directly());
+		}
 		vec.push(self.algorithm.clone() as u8);
-		vec.append(&mut self.encrypted_encryption_key.encode_directly());
+		vec.push(self.mode.get_value());
+		vec.append(&mut self.encryption_base_nonce.encode_directly());
 		vec.append(&mut self.encrypted_header_nonce.encode_directly());
 		vec
 	}
@@ -126,18 +177,27 @@ impl HeaderDecoder for EncryptionHeader {
 	fn decode_content(data: Vec<u8>) -> Result<EncryptionHeader> {
 		let mut cursor = Cursor::new(data);
 		let header_version = u8::decode_directly(&mut cursor)?;
-		let pbe_header = PBEHeader::decode_directly(&mut cursor)?;
+		let number_of_key_slots = u64::decode_directly(&mut cursor)?;
+		let mut key_slots = Vec::new();
+		for _ in 0..number_of_key_slots {
+			key_slots.push(KeySlot::decode_directly(&mut cursor)?);
+		}
 		let encryption_algorithm = match u8::decode_directly(&mut cursor)? {
 			0 => EncryptionAlgorithm::AES128GCMSIV,
 			1 => EncryptionAlgorithm::AES256GCMSIV,
+			2 => EncryptionAlgorithm::ChaCha20Poly1305,
 			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_ENCRYPTION_ALGORITHM)),
 		};
-		let key_length = u32::decode_directly(&mut cursor)? as usize;
-		let mut encryption_key = vec![0u8; key_length];
-		cursor.read_exact(&mut encryption_key)?;
+		let mode = match u8::decode_directly(&mut cursor)? {
+			0 => EncryptionMode::Streamed,
+			1 => EncryptionMode::InMemory,
+			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_ENCRYPTION_MODE)),
+		};
+		let mut encryption_base_nonce = [0; 8];
+		cursor.read_exact(&mut encryption_base_nonce)?;
 		let mut nonce = [0; 12];
 		cursor.read_exact(&mut nonce)?;
-		Ok(EncryptionHeader::new(header_version, pbe_header, encryption_algorithm, encryption_key, nonce))
+		Ok(EncryptionHeader::new(header_version, key_slots, encryption_algorithm, mode, encryption_base_nonce, nonce))
 	}
 }
 
@@ -148,10 +208,11 @@ impl Serialize for EncryptionHeader {
     {
         let mut state = serializer.serialize_struct("EncryptionHeader", 10)?;
         state.serialize_field("header_version", &self.header_version)?;
-        state.serialize_field("pbe_header", &self.pbe_header)?;
+        state.serialize_field("key_slots", &self.key_slots)?;
         state.serialize_field("algorithm", &self.algorithm)?;
-        state.serialize_field("encrypted_encryption_key", &self.encrypted_encryption_key.encode_hex::<String>())?;
-        state.serialize_field("encrypted_header_nonce", &self.encrypted_encryption_key.encode_hex::<String>())?;
+        state.serialize_field("mode", &self.mode)?;
+        state.serialize_field("encryption_base_nonce", &self.encryption_base_nonce.encode_hex::<String>())?;
+        state.serialize_field("encrypted_header_nonce", &self.encrypted_header_nonce.encode_hex::<String>())?;
         state.end()
     }
-}
\ No newline at end of file
+}
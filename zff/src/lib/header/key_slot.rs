@@ -0,0 +1,142 @@
+// - STD
+use std::io::Cursor;
+
+// - internal
+use crate::{
+	Result,
+	HeaderObject,
+	HeaderEncoder,
+	HeaderDecoder,
+	ValueEncoder,
+	ValueDecoder,
+	ZffError,
+	header::PBEHeader,
+	header::KDFScheme,
+	header::PBEScheme,
+	header::KDFParameters,
+	Encryption,
+	HEADER_IDENTIFIER_KEY_SLOT,
+	ERROR_HEADER_DECODER_MISMATCH_IDENTIFIER_KDF,
+};
+
+// - external
+use serde::Serialize;
+
+/// A single keyslot of an [crate::header::EncryptionHeader]: a [PBEHeader] (with its own salt/KDF
+/// parameters) together with its own wrapped copy of the image's master encryption key.\
+/// An [crate::header::EncryptionHeader] carries a [Vec] of keyslots, so one image can be unlocked
+/// by several independent passwords/keys.
+#[derive(Debug,Clone,Serialize)]
+pub struct KeySlot {
+	pbe_header: PBEHeader,
+	encrypted_encryption_key: Vec<u8>,
+}
+
+impl KeySlot {
+	/// creates a new keyslot by the given values.
+	pub fn new(pbe_header: PBEHeader, encrypted_encryption_key: Vec<u8>) -> KeySlot {
+		Self {
+			pbe_header: pbe_header,
+			encrypted_encryption_key: encrypted_encryption_key,
+		}
+	}
+
+	/// returns a reference to the inner PBE header.
+	pub fn pbe_header(&self) -> &PBEHeader {
+		&self.pbe_header
+	}
+
+	/// returns the wrapped (encrypted) master encryption key.
+	pub fn encrypted_encryption_key(&self) -> &Vec<u8> {
+		&self.encrypted_encryption_key
+	}
+
+	/// tries to unwrap the master encryption key with the given password. Returns an error if
+	/// unwrapping fails; a wrong password is detected only by the AES-CBC padding check, so a
+	/// wrong password whose output happens to carry valid padding is not detected.
+	pub fn decrypt_encryption_key<P: AsRef<[u8]>>(&self, password: P) -> Result<Vec<u8>> {
+		match self.pbe_header.kdf_scheme() {
+			KDFScheme::PBKDF2SHA256 => match self.pbe_header.kdf_parameters() {
+				KDFParameters::PBKDF2SHA256Parameters(parameters) => {
+					let iterations = parameters.iterations();
+					let salt = parameters.salt();
+
+					match self.pbe_header.encryption_scheme() {
+						PBEScheme::AES128CBC => Encryption::decrypt_pbkdf2sha256_aes128cbc(
+							iterations,
+							salt,
+							self.pbe_header.nonce(),
+							password,
+							&self.encrypted_encryption_key
+							),
+						PBEScheme::AES256CBC => Encryption::decrypt_pbkdf2sha256_aes256cbc(
+							iterations,
+							salt,
+							self.pbe_header.nonce(),
+							password,
+							&self.encrypted_encryption_key
+							),
+					}
+				}
+				KDFParameters::Argon2idParameters(_) => Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_MISMATCH_IDENTIFIER_KDF)),
+			}
+			KDFScheme::Argon2id => match self.pbe_header.kdf_parameters() {
+				KDFParameters::Argon2idParameters(parameters) => {
+					let m_cost = parameters.m_cost();
+					let t_cost = parameters.t_cost();
+					let p_cost = parameters.p_cost();
+					let salt = parameters.salt();
+
+					match self.pbe_header.encryption_scheme() {
+						PBEScheme::AES128CBC => Encryption::decrypt_argon2id_aes128cbc(
+							m_cost,
+							t_cost,
+							p_cost,
+							salt,
+							self.pbe_header.nonce(),
+							password,
+							&self.encrypted_encryption_key
+							),
+						PBEScheme::AES256CBC => Encryption::decrypt_argon2id_aes256cbc(
+							m_cost,
+							t_cost,
+							p_cost,
+							salt,
+							self.pbe_header.nonce(),
+							password,
+							&self.encrypted_encryption_key
+							),
+					}
+				}
+				KDFParameters::PBKDF2SHA256Parameters(_) => Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_MISMATCH_IDENTIFIER_KDF)),
+			}
+		}
+	}
+}
+
+impl HeaderObject for KeySlot {
+	fn identifier() -> u32 {
+		HEADER_IDENTIFIER_KEY_SLOT
+	}
+	fn encode_header(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+
+		vec.append(&mut self.pbe_header.encode_directly());
+		vec.append(&mut self.encrypted_encryption_key.encode_directly());
+
+		vec
+	}
+}
+
+impl HeaderEncoder for KeySlot {}
+
+impl HeaderDecoder for KeySlot {
+	type Item = KeySlot;
+
+	fn decode_content(data: Vec<u8>) -> Result<KeySlot> {
+		let mut cursor = Cursor::new(data);
+		let pbe_header = PBEHeader::decode_directly(&mut cursor)?;
+		let encrypted_encryption_key = Vec::<u8>::decode_directly(&mut cursor)?;
+		Ok(KeySlot::new(pbe_header, encrypted_encryption_key))
+	}
+}
@@ -0,0 +1,280 @@
+// - STD
+use std::io::{Cursor, Read};
+
+// - internal
+use crate::{
+	Result,
+	HeaderObject,
+	HeaderEncoder,
+	HeaderDecoder,
+	ValueEncoder,
+	ValueDecoder,
+	ZffError,
+	HEADER_IDENTIFIER_PBE_HEADER,
+	PBE_KDF_PARAMETERS,
+	PBE_KDF_PARAMETERS_ARGON2ID,
+	ERROR_HEADER_DECODER_UNKNOWN_PBE_SCHEME,
+	ERROR_HEADER_DECODER_UNKNOWN_KDF_SCHEME,
+	ERROR_HEADER_DECODER_INVALID_KDF_PARAMETER,
+};
+
+// - external
+use serde::Serialize;
+
+/// The PBE header contains all informations about the used password-based-encryption,
+/// e.g. the used key derivation function and its parameters (e.g. salt, iterations, ...)
+/// and the used encryption scheme to encrypt the encryption key itself.
+#[derive(Debug,Clone,Serialize)]
+pub struct PBEHeader {
+	header_version: u8,
+	kdf_scheme: KDFScheme,
+	encryption_scheme: PBEScheme,
+	kdf_parameters: KDFParameters,
+	pbencryption_nonce: [u8; 16],
+}
+
+impl PBEHeader {
+	/// creates a new PBEHeader by the given values.
+	pub fn new(
+		header_version: u8,
+		kdf_scheme: KDFScheme,
+		encryption_scheme: PBEScheme,
+		kdf_parameters: KDFParameters,
+		pbencryption_nonce: [u8; 16],
+		) -> PBEHeader {
+		Self {
+			header_version: header_version,
+			kdf_scheme: kdf_scheme,
+			encryption_scheme: encryption_scheme,
+			kdf_parameters: kdf_parameters,
+			pbencryption_nonce: pbencryption_nonce,
+		}
+	}
+
+	/// returns the used kdf scheme.
+	pub fn kdf_scheme(&self) -> &KDFScheme {
+		&self.kdf_scheme
+	}
+
+	/// returns a reference to the used kdf parameters.
+	pub fn kdf_parameters(&self) -> &KDFParameters {
+		&self.kdf_parameters
+	}
+
+	/// returns the used encryption scheme (to encrypt the encryption key itself).
+	pub fn encryption_scheme(&self) -> &PBEScheme {
+		&self.encryption_scheme
+	}
+
+	/// returns the nonce/iv, used to encrypt the encryption key.
+	pub fn nonce(&self) -> &[u8; 16] {
+		&self.pbencryption_nonce
+	}
+}
+
+impl HeaderObject for PBEHeader {
+	fn identifier() -> u32 {
+		HEADER_IDENTIFIER_PBE_HEADER
+	}
+	fn encode_header(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+
+		vec.push(self.header_version);
+		vec.push(self.kdf_scheme.clone() as u8);
+		vec.push(self.encryption_scheme.clone() as u8);
+		vec.append(&mut self.kdf_parameters.encode_directly());
+		vec.append(&mut self.pbencryption_nonce.encode_directly());
+
+		vec
+	}
+}
+
+impl HeaderEncoder for PBEHeader {}
+
+impl HeaderDecoder for PBEHeader {
+	type Item = PBEHeader;
+
+	fn decode_content(data: Vec<u8>) -> Result<PBEHeader> {
+		let mut cursor = Cursor::new(data);
+		let header_version = u8::decode_directly(&mut cursor)?;
+		let kdf_scheme = match u8::decode_directly(&mut cursor)? {
+			0 => KDFScheme::PBKDF2SHA256,
+			1 => KDFScheme::Argon2id,
+			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_KDF_SCHEME)),
+		};
+		let encryption_scheme = match u8::decode_directly(&mut cursor)? {
+			0 => PBEScheme::AES128CBC,
+			1 => PBEScheme::AES256CBC,
+			_ => return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_UNKNOWN_PBE_SCHEME)),
+		};
+		let kdf_parameters = match kdf_scheme {
+			KDFScheme::PBKDF2SHA256 => KDFParameters::PBKDF2SHA256Parameters(PBKDF2SHA256Parameters::decode_directly(&mut cursor)?),
+			KDFScheme::Argon2id => KDFParameters::Argon2idParameters(Argon2idParameters::decode_directly(&mut cursor)?),
+		};
+		let mut pbencryption_nonce = [0; 16];
+		cursor.read_exact(&mut pbencryption_nonce)?;
+		Ok(PBEHeader::new(header_version, kdf_scheme, encryption_scheme, kdf_parameters, pbencryption_nonce))
+	}
+}
+
+/// Defines the recognized key derivation function (KDF) schemes a [PBEHeader] may carry.
+#[derive(Debug,Clone,Serialize)]
+pub enum KDFScheme {
+	/// PBKDF2 with HMAC-SHA256.
+	PBKDF2SHA256,
+	/// Argon2id, a memory-hard KDF, recommended for long-term storage of forensic images.
+	Argon2id,
+}
+
+/// Defines the used encryption scheme, which is used to encrypt the encryption key itself.
+#[derive(Debug,Clone,Serialize)]
+pub enum PBEScheme {
+	AES128CBC,
+	AES256CBC,
+}
+
+/// Wraps the KDF-specific parameters, depending on the used [KDFScheme].
+#[derive(Debug,Clone,Serialize)]
+pub enum KDFParameters {
+	PBKDF2SHA256Parameters(PBKDF2SHA256Parameters),
+	Argon2idParameters(Argon2idParameters),
+}
+
+impl KDFParameters {
+	pub fn encode_directly(&self) -> Vec<u8> {
+		match self {
+			KDFParameters::PBKDF2SHA256Parameters(parameters) => parameters.encode_directly(),
+			KDFParameters::Argon2idParameters(parameters) => parameters.encode_directly(),
+		}
+	}
+}
+
+/// The parameters used for the PBKDF2-SHA256 key derivation.
+#[derive(Debug,Clone,Serialize)]
+pub struct PBKDF2SHA256Parameters {
+	iterations: u16,
+	salt: [u8; 32],
+}
+
+impl PBKDF2SHA256Parameters {
+	pub fn new(iterations: u16, salt: [u8; 32]) -> PBKDF2SHA256Parameters {
+		Self {
+			iterations: iterations,
+			salt: salt,
+		}
+	}
+
+	/// returns the number of iterations, used for the key derivation.
+	pub fn iterations(&self) -> u16 {
+		self.iterations
+	}
+
+	/// returns the salt, used for the key derivation.
+	pub fn salt(&self) -> &[u8; 32] {
+		&self.salt
+	}
+}
+
+impl HeaderObject for PBKDF2SHA256Parameters {
+	fn identifier() -> u32 {
+		PBE_KDF_PARAMETERS
+	}
+	fn encode_header(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+
+		vec.append(&mut self.iterations.encode_directly());
+		vec.append(&mut self.salt.encode_directly());
+
+		vec
+	}
+}
+
+impl HeaderEncoder for PBKDF2SHA256Parameters {}
+
+impl HeaderDecoder for PBKDF2SHA256Parameters {
+	type Item = PBKDF2SHA256Parameters;
+
+	fn decode_content(data: Vec<u8>) -> Result<PBKDF2SHA256Parameters> {
+		let mut cursor = Cursor::new(data);
+		let iterations = u16::decode_directly(&mut cursor)?;
+		let mut salt = [0; 32];
+		cursor.read_exact(&mut salt)?;
+		Ok(PBKDF2SHA256Parameters::new(iterations, salt))
+	}
+}
+
+/// The parameters used for the Argon2id key derivation.\
+/// `m_cost` is the memory size in KiB, `t_cost` the number of iterations and `p_cost`
+/// the degree of parallelism (lanes).
+#[derive(Debug,Clone,Serialize)]
+pub struct Argon2idParameters {
+	m_cost: u32,
+	t_cost: u32,
+	p_cost: u32,
+	salt: Vec<u8>,
+}
+
+impl Argon2idParameters {
+	pub fn new(m_cost: u32, t_cost: u32, p_cost: u32, salt: Vec<u8>) -> Argon2idParameters {
+		Self {
+			m_cost: m_cost,
+			t_cost: t_cost,
+			p_cost: p_cost,
+			salt: salt,
+		}
+	}
+
+	/// returns the memory cost factor (in KiB).
+	pub fn m_cost(&self) -> u32 {
+		self.m_cost
+	}
+
+	/// returns the iteration cost factor.
+	pub fn t_cost(&self) -> u32 {
+		self.t_cost
+	}
+
+	/// returns the parallelism (lanes) cost factor.
+	pub fn p_cost(&self) -> u32 {
+		self.p_cost
+	}
+
+	/// returns the salt, used for the key derivation.
+	pub fn salt(&self) -> &Vec<u8> {
+		&self.salt
+	}
+}
+
+impl HeaderObject for Argon2idParameters {
+	fn identifier() -> u32 {
+		PBE_KDF_PARAMETERS_ARGON2ID
+	}
+	fn encode_header(&self) -> Vec<u8> {
+		let mut vec = Vec::new();
+
+		vec.append(&mut self.m_cost.encode_directly());
+		vec.append(&mut self.t_cost.encode_directly());
+		vec.append(&mut self.p_cost.encode_directly());
+		vec.append(&mut self.salt.encode_directly());
+
+		vec
+	}
+}
+
+impl HeaderEncoder for Argon2idParameters {}
+
+impl HeaderDecoder for Argon2idParameters {
+	type Item = Argon2idParameters;
+
+	fn decode_content(data: Vec<u8>) -> Result<Argon2idParameters> {
+		let mut cursor = Cursor::new(data);
+		let m_cost = u32::decode_directly(&mut cursor)?;
+		let t_cost = u32::decode_directly(&mut cursor)?;
+		let p_cost = u32::decode_directly(&mut cursor)?;
+		let salt = Vec::<u8>::decode_directly(&mut cursor)?;
+		if m_cost == 0 || t_cost == 0 || p_cost == 0 {
+			return Err(ZffError::new_header_decode_error(ERROR_HEADER_DECODER_INVALID_KDF_PARAMETER));
+		}
+		Ok(Argon2idParameters::new(m_cost, t_cost, p_cost, salt))
+	}
+}